@@ -0,0 +1,52 @@
+//! Benchmarks for the `verify_*` family on `c2_blockchain::p1_header_chain`, exercising
+//! the verifier against long, realistic chains.
+//!
+//! This tree has no `Cargo.toml` of its own to register this bench in. To run it,
+//! add `criterion` as a dev-dependency and a
+//! `[[bench]] name = "verify_benchmark" harness = false` entry to the crate's
+//! manifest, then run with `cargo bench --bench verify_benchmark`.
+
+use blockchain_from_scratch::c2_blockchain::p1_header_chain::{build_valid_chain, Header};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+const CHAIN_LENGTHS: [usize; 3] = [1_000, 10_000, 100_000];
+
+fn verify_all_valid(c: &mut Criterion) {
+    let mut group = c.benchmark_group("verify_sub_chain/all_valid");
+    for len in CHAIN_LENGTHS {
+        let chain = build_valid_chain(len);
+        group.bench_with_input(BenchmarkId::from_parameter(len), &chain, |b, chain| {
+            b.iter(|| chain[0].verify_sub_chain(black_box(&chain[1..])));
+        });
+    }
+    group.finish();
+}
+
+fn verify_early_rejection(c: &mut Criterion) {
+    let mut group = c.benchmark_group("verify_sub_chain/corrupted");
+    for len in CHAIN_LENGTHS {
+        // Swapping in a genesis header breaks both the parent-hash link and the
+        // height sequence, without needing access to `Header`'s private fields.
+        let mut corrupted_at_front = build_valid_chain(len);
+        corrupted_at_front[1] = Header::genesis();
+
+        let mut corrupted_at_tail = build_valid_chain(len);
+        let tail = corrupted_at_tail.len() - 1;
+        corrupted_at_tail[tail] = Header::genesis();
+
+        group.bench_with_input(
+            BenchmarkId::new("corrupted_at_front", len),
+            &corrupted_at_front,
+            |b, chain| b.iter(|| chain[0].verify_sub_chain(black_box(&chain[1..]))),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("corrupted_at_tail", len),
+            &corrupted_at_tail,
+            |b, chain| b.iter(|| chain[0].verify_sub_chain(black_box(&chain[1..]))),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, verify_all_valid, verify_early_rejection);
+criterion_main!(benches);
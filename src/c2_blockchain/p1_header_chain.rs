@@ -3,6 +3,8 @@
 //! start with that.
 //!
 
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
 use std::iter;
 
 use crate::hash;
@@ -20,73 +22,442 @@ pub struct Header {
     extrinsics_root: (),
     state_root: (),
     consensus_digest: (),
+    /// Hashes of stale sibling headers ("uncles" or "ommers") that this block
+    /// references, typically to extend a partial reward to the miners/validators
+    /// who produced them.
+    uncles: Vec<Hash>,
+}
+
+/// The maximum number of uncles a single header may reference.
+pub const MAXIMUM_UNCLE_COUNT: usize = 2;
+
+/// The ways in which header verification can fail. Each variant carries enough
+/// information for a caller to explain precisely why a chain was rejected, instead
+/// of just getting back `false`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VerificationError {
+    /// A header's height was not exactly one greater than its parent's.
+    HeightMismatch { expected: u64, found: u64 },
+    /// A header's `parent` field did not match the hash of the header that precedes it.
+    ParentMismatch { expected: Hash, found: Hash },
+    /// A header referenced more uncles than `MAXIMUM_UNCLE_COUNT` allows.
+    TooManyUncles { max: usize, found: usize },
+    /// A referenced uncle's height was not strictly less than the including block's.
+    InvalidUncleHeight { uncle_height: u64, block_height: u64 },
 }
 
 // Here are the methods for creating a new header and verifying headers.
 // It is your job to write them.
 impl Header {
     /// Returns a new valid genesis header.
-    fn genesis() -> Self {
+    pub fn genesis() -> Self {
         let height = 0;
         let parent = self::Hash::default();
-        Header{parent,height,extrinsics_root:(),state_root:(),consensus_digest:()}
+        Header{parent,height,extrinsics_root:(),state_root:(),consensus_digest:(),uncles:Vec::new()}
     }
 
     /// Create and return a valid child header.
-    fn child(&self) -> Self {
-        let parent_block = Self::genesis();
-        let parent = hash(&parent_block);
-        let height = parent_block.height+1;
-        Header{parent,height,..parent_block}
+    pub fn child(&self) -> Self {
+        let parent = hash(self);
+        let height = self.height + 1;
+        Header{parent,height,uncles:Vec::new(),..self.clone()}
+    }
+
+    /// Create and return a valid child header that also references `uncles`: the
+    /// hashes of stale sibling headers this child includes for a partial reward.
+    pub fn child_with_uncles(&self, uncles: Vec<Hash>) -> Self {
+        let mut child = self.child();
+        child.uncles = uncles;
+        child
+    }
+
+    /// Verify the invariants that can be checked about this header in isolation,
+    /// without reference to any other header. This is the cheap first phase of the
+    /// verification pipeline below: fast, stateless checks run before anything
+    /// that needs to walk the chain.
+    pub fn verify_header_basic(&self) -> Result<(), VerificationError> {
+        if self.uncles.len() > MAXIMUM_UNCLE_COUNT {
+            return Err(VerificationError::TooManyUncles {
+                max: MAXIMUM_UNCLE_COUNT,
+                found: self.uncles.len(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Verify the invariants that require resolving this header's uncle hashes to
+    /// their actual headers -- namely that every uncle is older than the block
+    /// that includes it. This is the "family" stage of the pipeline: the caller
+    /// looks the referenced uncles up first (`BlockTree::import_header` does, from
+    /// its own store) and passes in whichever of them it could resolve.
+    fn verify_uncles(&self, uncles: &[Header]) -> Result<(), VerificationError> {
+        for uncle in uncles {
+            if uncle.height >= self.height {
+                return Err(VerificationError::InvalidUncleHeight {
+                    uncle_height: uncle.height,
+                    block_height: self.height,
+                });
+            }
+        }
+
+        Ok(())
     }
 
     /// Verify that all the given headers form a valid chain from this header to the tip.
     /// An "entire" chain can be verified by calling this method on a genesis header.
     /// This method may assume that the block on which it is called is valid, but it
-    /// must verify all of the blocks in the slice;
-    fn verify_sub_chain(&self, chain: &[Header]) -> bool {
-        let parent_block = self;
-        let genesis_hash = hash(parent_block);
-        let mut check = true; 
-        
-        if chain.len()>0{
-            check = (genesis_hash ==chain[0].parent) && (parent_block.height==chain[0].height-1); 
-           if check==true{
-            for i in 0..chain.len()-1 {            
-                let hash0 = hash(&chain[i]);
-                if hash0 == chain[i+1].parent{
-                    check=true;
+    /// must verify all of the blocks in the slice.
+    pub fn verify_sub_chain(&self, chain: &[Header]) -> Result<(), VerificationError> {
+        let mut parent = self;
+
+        for child in chain {
+            parent.verify_header_basic()?;
+
+            let parent_hash = hash(parent);
+            if parent_hash != child.parent {
+                return Err(VerificationError::ParentMismatch {
+                    expected: parent_hash,
+                    found: child.parent,
+                });
+            }
+
+            if child.height != parent.height + 1 {
+                return Err(VerificationError::HeightMismatch {
+                    expected: parent.height + 1,
+                    found: child.height,
+                });
+            }
+
+            parent = child;
+        }
+
+        parent.verify_header_basic()
+    }
+}
+
+/// A tree of headers connected by parent links, capable of holding several competing
+/// branches at once. Unlike a plain `Vec<Header>`, which can only represent one linear
+/// history, this lets us ingest headers as they arrive -- possibly out of order, possibly
+/// building on a block that later turns out to be an uncle -- and still answer "which tip
+/// is canonical?" via a heaviest-subtree fork-choice rule.
+pub struct BlockTree {
+    /// Every header we know about, keyed by its own hash.
+    headers: HashMap<Hash, Header>,
+    /// For each header hash, the hashes of the headers that name it as `parent`.
+    children: HashMap<Hash, Vec<Hash>>,
+    /// The hash of the genesis header this tree is rooted at.
+    genesis: Hash,
+}
+
+impl BlockTree {
+    /// Create a new tree containing only the given genesis header.
+    pub fn new(genesis: Header) -> Self {
+        let genesis_hash = hash(&genesis);
+        let mut headers = HashMap::new();
+        headers.insert(genesis_hash, genesis);
+
+        BlockTree {
+            headers,
+            children: HashMap::new(),
+            genesis: genesis_hash,
+        }
+    }
+
+    /// Ingest a header into the tree, recording it as a child of its parent, after
+    /// checking that any uncles it references -- among the headers, from any
+    /// branch, that this tree already knows about -- are actually older than it.
+    /// Headers may be imported in any order; this only assumes that the header's
+    /// `parent` field is the hash of a block that will (eventually) be in the tree.
+    /// An uncle hash this tree doesn't recognize yet is left unchecked rather than
+    /// rejected, since out-of-order import means it may simply not have arrived.
+    pub fn import_header(&mut self, header: Header) -> Result<(), VerificationError> {
+        header.verify_header_basic()?;
+
+        let known_uncles: Vec<Header> = header
+            .uncles
+            .iter()
+            .filter_map(|uncle_hash| self.headers.get(uncle_hash).cloned())
+            .collect();
+        header.verify_uncles(&known_uncles)?;
+
+        let header_hash = hash(&header);
+        let parent = header.parent;
+        self.headers.insert(header_hash, header);
+        self.children.entry(parent).or_default().push(header_hash);
+        Ok(())
+    }
+
+    /// The weight of every header's subtree: the number of headers, including the
+    /// header itself, that descend from it. Computed bottom-up in a single pass over
+    /// an explicit stack rather than per-node recursion, so a long chain of tens of
+    /// thousands of headers doesn't blow the call stack or get re-summed once per
+    /// level of descent in `best_head`.
+    fn subtree_weights(&self) -> HashMap<Hash, usize> {
+        let mut post_order = Vec::new();
+        let mut stack = vec![self.genesis];
+        while let Some(block) = stack.pop() {
+            post_order.push(block);
+            if let Some(children) = self.children.get(&block) {
+                stack.extend(children.iter().copied());
             }
-           }
-            
         }
+
+        let mut weights = HashMap::new();
+        for block in post_order.into_iter().rev() {
+            let children_weight: usize = self
+                .children
+                .get(&block)
+                .into_iter()
+                .flatten()
+                .map(|child| weights[child])
+                .sum();
+            weights.insert(block, 1 + children_weight);
         }
-        
-        check
+
+        weights
+    }
+
+    /// Select the canonical head of the tree. Starting at genesis, repeatedly descend
+    /// into the child with the greatest subtree weight, breaking ties by lowest hash
+    /// for determinism, until a leaf is reached.
+    pub fn best_head(&self) -> Hash {
+        let weights = self.subtree_weights();
+        let mut head = self.genesis;
+
+        loop {
+            let children = match self.children.get(&head) {
+                Some(children) if !children.is_empty() => children,
+                _ => return head,
+            };
+
+            head = *children
+                .iter()
+                .max_by_key(|child| (weights[child], Reverse(**child)))
+                .expect("children is non-empty, checked above");
+        }
+    }
+
+    /// Reconstruct the ancestor list from genesis up to and including `tip`, by
+    /// following `parent` pointers backward from `tip` and then reversing.
+    pub fn chain_from_genesis(&self, tip: Hash) -> Vec<Header> {
+        let mut chain = Vec::new();
+        let mut current = tip;
+
+        while let Some(header) = self.headers.get(&current) {
+            let parent = header.parent;
+            chain.push(header.clone());
+
+            if current == self.genesis {
+                break;
+            }
+            current = parent;
+        }
+
+        chain.reverse();
+        chain
     }
 }
 
-// And finally a few functions to use the code we just
+/// A number of blocks per CHT epoch: a round number of headers to fold into one
+/// committed root at a time. [`HeaderChain::pruned`] does not apply it on its own;
+/// the epoch size is always passed in explicitly.
+pub const DEFAULT_EPOCH_SIZE: u64 = 2048;
 
-/// Build and return a valid chain with exactly five blocks including the genesis block.
-fn build_valid_chain_length_5() -> Vec<Header> {
-    let mut chain:Vec<Header> = Vec::new();
-    let new_header = Header::genesis();
-    chain.push(new_header.clone());
-    let first_child = new_header.child();
-    chain.push(first_child);
+/// An indexed store of a single canonical chain. Where `BlockTree` holds every
+/// competing branch, `HeaderChain` holds only the chain that's been chosen as
+/// canonical, indexed for O(1) lookups by hash and by height instead of re-hashing
+/// ancestors on every query.
+///
+/// When built with [`HeaderChain::pruned`], it also bounds its own memory use: once
+/// `epoch_size` canonical headers have piled up, they are folded into a single CHT
+/// (canonical hash tree) root and dropped, keeping only the roots plus the current
+/// epoch's headers.
+pub struct HeaderChain {
+    /// Every still-retained canonical header, keyed by its own hash.
+    headers: HashMap<Hash, Header>,
+    /// Canonical height -> hash for still-retained headers, so a header can be found
+    /// by height without walking parent pointers.
+    heights: BTreeMap<u64, Hash>,
+    /// The canonical tip. Kept outside of `headers`/`heights` so it remains
+    /// reachable even the instant its epoch is folded and pruned away.
+    tip: Header,
+    /// `Some(epoch_size)` enables pruning; `None` retains every header forever.
+    epoch_size: Option<u64>,
+    /// Committed roots for epochs that have already been folded, keyed by epoch
+    /// index (`height / epoch_size`).
+    cht_roots: BTreeMap<u64, Hash>,
+}
 
-    for i in 2..5{
-        let length = chain.len();
-        let parent = hash(&chain[length-1]);
-        let height = chain[length-1].height+1;
-        let child_header = Header{parent,height,..chain[length-1]};
-        chain.push(child_header); 
+impl HeaderChain {
+    /// Create a new chain store rooted at the given genesis header, retaining every
+    /// header it is ever given.
+    pub fn new(genesis: Header) -> Self {
+        Self::new_with_epoch_size(genesis, None)
+    }
+
+    /// Create a new chain store that prunes into CHT roots once every `epoch_size`
+    /// canonical headers fill an epoch, bounding memory use for long chains.
+    pub fn pruned(genesis: Header, epoch_size: u64) -> Self {
+        Self::new_with_epoch_size(genesis, Some(epoch_size))
+    }
+
+    fn new_with_epoch_size(genesis: Header, epoch_size: Option<u64>) -> Self {
+        let genesis_hash = hash(&genesis);
+        let mut headers = HashMap::new();
+        let mut heights = BTreeMap::new();
+        heights.insert(genesis.height, genesis_hash);
+        headers.insert(genesis_hash, genesis.clone());
+
+        HeaderChain {
+            headers,
+            heights,
+            tip: genesis,
+            epoch_size,
+            cht_roots: BTreeMap::new(),
+        }
+    }
+
+    /// Look up a still-retained header by its hash.
+    pub fn header_by_hash(&self, hash: &Hash) -> Option<Header> {
+        self.headers.get(hash).cloned()
+    }
+
+    /// Look up the canonical hash at a still-retained height.
+    pub fn hash_by_height(&self, height: u64) -> Option<Hash> {
+        self.heights.get(&height).copied()
+    }
+
+    /// Look up the canonical header at a still-retained height.
+    pub fn header_by_height(&self, height: u64) -> Option<Header> {
+        self.hash_by_height(height)
+            .and_then(|hash| self.header_by_hash(&hash))
+    }
+
+    /// The current canonical tip. Always available, even under pruning.
+    pub fn tip(&self) -> Header {
+        self.tip.clone()
+    }
+
+    /// Append `header` to the canonical chain, validating that it links up with the
+    /// current tip and that any uncles it references are actually older than it,
+    /// before indexing it, then folding the previous epoch if pruning is enabled
+    /// and that epoch has just filled. Uncles this chain doesn't have a header for
+    /// (e.g. because their epoch has already been folded and pruned) are left
+    /// unchecked, the same as `BlockTree::import_header` leaves unresolved ones.
+    pub fn insert(&mut self, header: Header) -> Result<(), VerificationError> {
+        self.tip.verify_sub_chain(std::slice::from_ref(&header))?;
+
+        let known_uncles: Vec<Header> = header
+            .uncles
+            .iter()
+            .filter_map(|uncle_hash| self.headers.get(uncle_hash).cloned())
+            .collect();
+        header.verify_uncles(&known_uncles)?;
+
+        let header_hash = hash(&header);
+        self.heights.insert(header.height, header_hash);
+        self.headers.insert(header_hash, header.clone());
+        self.tip = header;
+
+        if let Some(epoch_size) = self.epoch_size {
+            self.fold_completed_epoch(epoch_size);
+        }
+        Ok(())
+    }
+
+    /// If the tip has just become the first header of a new epoch, fold the epoch
+    /// that precedes it into a single committed root and drop its headers.
+    fn fold_completed_epoch(&mut self, epoch_size: u64) {
+        let tip_height = self.tip.height;
+        if tip_height == 0 || !tip_height.is_multiple_of(epoch_size) {
+            return;
+        }
+
+        let epoch_index = tip_height / epoch_size - 1;
+        let start = epoch_index * epoch_size;
+        let end = start + epoch_size - 1;
+
+        let pairs: Vec<(u64, Hash)> = self
+            .heights
+            .range(start..=end)
+            .map(|(height, hash)| (*height, *hash))
+            .collect();
+        if pairs.is_empty() {
+            return;
+        }
+
+        self.cht_roots.insert(epoch_index, hash(&pairs));
+        for (height, header_hash) in pairs {
+            self.heights.remove(&height);
+            self.headers.remove(&header_hash);
+        }
+    }
+
+    /// The committed root for `epoch_index`, if that epoch has been folded.
+    pub fn cht_root(&self, epoch_index: u64) -> Option<Hash> {
+        self.cht_roots.get(&epoch_index).copied()
     }
 
+    /// Produce a membership proof for a header that is still retained. The proof
+    /// can be checked later, once its epoch has been folded and pruned away, with
+    /// [`ChtProof::verify_cht_proof`]. The proof holds the epoch's pairs as they are
+    /// at the time of the call, so it only matches the folded root if the epoch was
+    /// already full then; a proof taken from a partly filled epoch does not verify.
+    pub fn prove_header(&self, height: u64) -> Option<(Header, ChtProof)> {
+        let epoch_size = self.epoch_size?;
+        let header = self.header_by_height(height)?;
+
+        let epoch_index = height / epoch_size;
+        let start = epoch_index * epoch_size;
+        let end = start + epoch_size - 1;
+        let pairs = self
+            .heights
+            .range(start..=end)
+            .map(|(h, hash)| (*h, *hash))
+            .collect();
+
+        Some((header, ChtProof { pairs }))
+    }
+}
+
+/// A proof that a `(height, hash)` pair was committed into a CHT root: the full
+/// sorted list of pairs in that epoch. This toy store folds an epoch by hashing its
+/// whole pair list rather than building a real Merkle tree, so the proof is simply
+/// that list; a production CHT would use a Merkle branch instead.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChtProof {
+    pairs: Vec<(u64, Hash)>,
+}
+
+impl ChtProof {
+    /// Confirm that `(height, hash)` was committed into the epoch whose root is `root`.
+    pub fn verify_cht_proof(&self, root: Hash, height: u64, target_hash: Hash) -> bool {
+        hash(&self.pairs) == root && self.pairs.contains(&(height, target_hash))
+    }
+}
+
+// And finally a few functions to use the code we just
+
+/// Build and return a valid chain of `n` headers, including the genesis header.
+/// The genesis header is always present, so an `n` of 0 also yields one header.
+/// Generalizes `build_valid_chain_length_5` so callers -- including the benches in
+/// `benches/verify_benchmark.rs` -- can generate chains of whatever length they need.
+pub fn build_valid_chain(n: usize) -> Vec<Header> {
+    let mut chain = Vec::with_capacity(n);
+    chain.push(Header::genesis());
+
+    while chain.len() < n {
+        let next = chain.last().expect("genesis was just pushed above").child();
+        chain.push(next);
+    }
 
     chain
+}
 
+/// Build and return a valid chain with exactly five blocks including the genesis block.
+fn build_valid_chain_length_5() -> Vec<Header> {
+    build_valid_chain(5)
 }
 
 /// Build and return a chain with at least three headers.
@@ -104,7 +475,7 @@ fn build_an_invalid_chain() -> Vec<Header> {
         let length = chain.len();
         let parent = hash(&chain[length-1]);
         let height = chain[length-1].height+1;
-        let child_header = Header{parent,height,..chain[length-1]};
+        let child_header = Header{parent,height,uncles:Vec::new(),..chain[length-1].clone()};
         chain.push(child_header); 
     }
 
@@ -143,7 +514,7 @@ fn bc_1_child_block_parent() {
 fn bc_1_verify_genesis_only() {
     let g = Header::genesis();
 
-    assert!(g.verify_sub_chain(&[]));
+    assert!(g.verify_sub_chain(&[]).is_ok());
 }
 
 #[test]
@@ -152,7 +523,7 @@ fn bc_1_verify_three_blocks() {
     let b1 = g.child();
     let b2 = b1.child();
 
-    assert!(g.verify_sub_chain(&[b1, b2]));
+    assert!(g.verify_sub_chain(&[b1, b2]).is_ok());
 }
 
 #[test]
@@ -163,7 +534,10 @@ fn bc_1_cant_verify_invalid_height() {
     let mut b1 = g.child();
     b1.height = 10;
 
-    assert!(!g.verify_sub_chain(&[b1]))
+    assert_eq!(
+        g.verify_sub_chain(&[b1]),
+        Err(VerificationError::HeightMismatch { expected: 1, found: 10 })
+    );
 }
 
 #[test]
@@ -174,7 +548,10 @@ fn bc_1_cant_verify_invalid_parent() {
     let mut b1 = g.child();
     b1.parent = 10;
 
-    assert!(!g.verify_sub_chain(&[b1]))
+    assert_eq!(
+        g.verify_sub_chain(&[b1]),
+        Err(VerificationError::ParentMismatch { expected: hash(&g), found: 10 })
+    );
 }
 
 #[test]
@@ -182,7 +559,7 @@ fn bc_1_verify_chain_length_five() {
     // This test chooses to use the student's own verify function.
     // This should be relatively safe given that we have already tested that function.
     let chain = build_valid_chain_length_5();
-    assert!(chain[0].verify_sub_chain(&chain[1..]))
+    assert!(chain[0].verify_sub_chain(&chain[1..]).is_ok())
 }
 
 #[test]
@@ -190,5 +567,219 @@ fn bc_1_invalid_chain_is_really_invalid() {
     // This test chooses to use the student's own verify function.
     // This should be relatively safe given that we have already tested that function.
     let invalid_chain = build_an_invalid_chain();
-    assert!(!invalid_chain[0].verify_sub_chain(&invalid_chain[1..]))
+    assert!(invalid_chain[0].verify_sub_chain(&invalid_chain[1..]).is_err())
+}
+
+#[test]
+fn bc_1_fork_choice_picks_heaviest_branch() {
+    let genesis = Header::genesis();
+    let mut tree = BlockTree::new(genesis.clone());
+
+    // A two-block branch through `b1`...
+    let b1 = genesis.child();
+    let b1b = b1.child();
+    tree.import_header(b1.clone()).unwrap();
+    tree.import_header(b1b.clone()).unwrap();
+
+    // ...and a single-block branch through `c1`. Its height is bumped so it doesn't
+    // collide with `b1`'s hash; `BlockTree::import_header` does no height validation,
+    // that's the job of `HeaderChain` and `verify_sub_chain`.
+    let mut c1 = genesis.child();
+    c1.height += 1;
+    tree.import_header(c1).unwrap();
+
+    assert_eq!(tree.best_head(), hash(&b1b));
+}
+
+#[test]
+fn bc_1_fork_choice_breaks_ties_by_lowest_hash() {
+    let genesis = Header::genesis();
+    let mut tree = BlockTree::new(genesis.clone());
+
+    let left = genesis.child();
+    let mut right = genesis.child();
+    right.height += 1; // distinct header, still a child of genesis, same subtree weight
+
+    tree.import_header(left.clone()).unwrap();
+    tree.import_header(right.clone()).unwrap();
+
+    let expected = std::cmp::min(hash(&left), hash(&right));
+    assert_eq!(tree.best_head(), expected);
+}
+
+#[test]
+fn bc_1_chain_from_genesis_reconstructs_ancestors() {
+    let genesis = Header::genesis();
+    let mut tree = BlockTree::new(genesis.clone());
+
+    let b1 = genesis.child();
+    let b2 = b1.child();
+    tree.import_header(b1.clone()).unwrap();
+    tree.import_header(b2.clone()).unwrap();
+
+    let chain = tree.chain_from_genesis(hash(&b2));
+    assert_eq!(chain, vec![genesis, b1, b2]);
+}
+
+#[test]
+fn bc_1_import_header_rejects_uncle_not_older_than_block() {
+    let genesis = Header::genesis();
+    let mut tree = BlockTree::new(genesis.clone());
+
+    // A sibling branch through `uncle`, a block that's actually a child of `b1`
+    // rather than older than it...
+    let b1 = genesis.child();
+    tree.import_header(b1.clone()).unwrap();
+    let uncle_from_the_future = b1.child();
+    tree.import_header(uncle_from_the_future.clone()).unwrap();
+
+    // ...which `b2` then mistakenly claims as an uncle.
+    let b2 = b1.child_with_uncles(vec![hash(&uncle_from_the_future)]);
+
+    assert_eq!(
+        tree.import_header(b2),
+        Err(VerificationError::InvalidUncleHeight {
+            uncle_height: uncle_from_the_future.height,
+            block_height: 2,
+        })
+    );
+}
+
+#[test]
+fn bc_1_import_header_rejects_too_many_uncles() {
+    let genesis = Header::genesis();
+    let mut tree = BlockTree::new(genesis.clone());
+
+    let too_many: Vec<Hash> = (0..=MAXIMUM_UNCLE_COUNT as u64).collect();
+    let b1 = genesis.child_with_uncles(too_many);
+
+    assert_eq!(
+        tree.import_header(b1),
+        Err(VerificationError::TooManyUncles {
+            max: MAXIMUM_UNCLE_COUNT,
+            found: MAXIMUM_UNCLE_COUNT + 1,
+        })
+    );
+}
+
+#[test]
+fn bc_1_header_chain_looks_up_by_hash_and_height() {
+    let genesis = Header::genesis();
+    let mut store = HeaderChain::new(genesis.clone());
+
+    let b1 = genesis.child();
+    store.insert(b1.clone()).unwrap();
+
+    assert_eq!(store.header_by_hash(&hash(&b1)), Some(b1.clone()));
+    assert_eq!(store.hash_by_height(1), Some(hash(&b1)));
+    assert_eq!(store.header_by_height(1), Some(b1.clone()));
+    assert_eq!(store.tip(), b1);
+}
+
+#[test]
+fn bc_1_header_chain_insert_rejects_wrong_parent() {
+    let genesis = Header::genesis();
+    let mut store = HeaderChain::new(genesis.clone());
+
+    let mut bad_child = genesis.child();
+    bad_child.parent = 10;
+
+    assert_eq!(
+        store.insert(bad_child),
+        Err(VerificationError::ParentMismatch {
+            expected: hash(&genesis),
+            found: 10
+        })
+    );
+    // The rejected header must not have been indexed.
+    assert_eq!(store.tip(), genesis);
+}
+
+#[test]
+fn bc_1_header_chain_insert_resolves_and_accepts_older_uncle() {
+    let genesis = Header::genesis();
+    let mut store = HeaderChain::new(genesis.clone());
+    store.insert(genesis.child()).unwrap(); // height 1; genesis (height 0) is the resolvable, older uncle below
+
+    let b1 = store.tip();
+    let b2 = b1.child_with_uncles(vec![hash(&genesis)]);
+
+    assert_eq!(store.insert(b2.clone()), Ok(()));
+    assert_eq!(store.tip(), b2);
+}
+
+#[test]
+fn bc_1_header_chain_prunes_into_cht_roots() {
+    let genesis = Header::genesis();
+    let mut store = HeaderChain::pruned(genesis.clone(), 2);
+
+    let b1 = genesis.child();
+    store.insert(b1.clone()).unwrap();
+
+    // Capture a proof for genesis while its epoch (heights 0 and 1) is still live.
+    let (proved_header, proof) = store.prove_header(0).unwrap();
+    assert_eq!(proved_header, genesis);
+
+    let b2 = b1.child();
+    store.insert(b2.clone()).unwrap(); // starts epoch 1, folding the full epoch 0 away
+
+    // The epoch's individual headers are gone...
+    assert_eq!(store.header_by_hash(&hash(&genesis)), None);
+    assert_eq!(store.header_by_height(0), None);
+    // ...but the tip, and the committed root, remain.
+    assert_eq!(store.tip(), b2);
+
+    let root = store.cht_root(0).expect("epoch 0 should have been folded");
+    assert!(proof.verify_cht_proof(root, 0, hash(&genesis)));
+    assert!(!proof.verify_cht_proof(root, 0, hash(&b1)));
+}
+
+#[test]
+fn bc_1_child_with_uncles_carries_them() {
+    let g = Header::genesis();
+    let uncle = g.child();
+    let b1 = g.child_with_uncles(vec![hash(&uncle)]);
+
+    assert_eq!(b1.uncles, vec![hash(&uncle)]);
+}
+
+#[test]
+fn bc_1_verify_sub_chain_rejects_too_many_uncles() {
+    let g = Header::genesis();
+    let too_many: Vec<Hash> = (0..=MAXIMUM_UNCLE_COUNT as u64).collect();
+    let b1 = g.child_with_uncles(too_many);
+
+    assert_eq!(
+        g.verify_sub_chain(&[b1]),
+        Err(VerificationError::TooManyUncles {
+            max: MAXIMUM_UNCLE_COUNT,
+            found: MAXIMUM_UNCLE_COUNT + 1
+        })
+    );
+}
+
+#[test]
+fn bc_1_verify_uncles_accepts_older_uncle() {
+    let g = Header::genesis();
+    let uncle = g.child(); // height 1
+    let b1 = g.child(); // also height 1, the main chain's block at that height
+    let b2 = b1.child_with_uncles(vec![hash(&uncle)]); // height 2, so the uncle is older
+
+    assert_eq!(b2.verify_uncles(&[uncle]), Ok(()));
+}
+
+#[test]
+fn bc_1_verify_uncles_rejects_uncle_not_older_than_block() {
+    let g = Header::genesis();
+    let b1 = g.child();
+    let uncle_from_the_future = b1.child();
+    let b2 = b1.child_with_uncles(vec![hash(&uncle_from_the_future)]);
+
+    assert_eq!(
+        b2.verify_uncles(std::slice::from_ref(&uncle_from_the_future)),
+        Err(VerificationError::InvalidUncleHeight {
+            uncle_height: uncle_from_the_future.height,
+            block_height: b2.height,
+        })
+    );
 }